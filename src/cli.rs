@@ -1,13 +1,24 @@
 use std::path::PathBuf;
 use structopt::StructOpt;
 
+use crate::tasks::{Format, Priority};
+
 #[derive(Debug, StructOpt)]
 pub enum Action {
     /// Write tasks to the journal file.
     Add {
         /// The task description text.
         #[structopt()]
-        text: String
+        text: String,
+        /// How urgently the task needs attention.
+        #[structopt(short, long, default_value = "low")]
+        priority: Priority,
+        /// A tag to attach to the task. Repeat to add more than one.
+        #[structopt(long = "tag")]
+        tags: Vec<String>,
+        /// The id of a task that must be completed first. Repeat for more than one.
+        #[structopt(long = "depends-on")]
+        dependencies: Vec<usize>,
     },
     /// Remove an entry from the journal file by position
     Done {
@@ -15,7 +26,11 @@ pub enum Action {
         position: usize
     },
     /// List incompleted tasks in the journal file
-    List,
+    List {
+        /// Only show tasks carrying this tag.
+        #[structopt(long)]
+        tag: Option<String>,
+    },
     /// Modify an existing entry using its position
     Edit {
         /// The position of the task to edit.
@@ -23,10 +38,50 @@ pub enum Action {
         position: usize,
         /// The new text to replace the old text.
         #[structopt()]
-        text: String
+        text: String,
+        /// Replace the task's tags. Repeat to add more than one.
+        #[structopt(long = "tag")]
+        tags: Vec<String>,
+        /// Replace the task's dependencies. Repeat for more than one.
+        #[structopt(long = "depends-on")]
+        dependencies: Vec<usize>,
     },
     /// List completed tasks in the journal file
     ListCompleted,
+    /// Log time spent on an incomplete task by position
+    Log {
+        /// The position of the task to log time against.
+        #[structopt()]
+        position: usize,
+        /// Hours spent.
+        #[structopt()]
+        hours: u16,
+        /// Minutes spent.
+        #[structopt()]
+        minutes: u16,
+    },
+    /// Move a task to Active and record when it was started
+    Start {
+        #[structopt()]
+        position: usize
+    },
+    /// Move an Active task back to Pending
+    Stop {
+        #[structopt()]
+        position: usize
+    },
+    /// Return a task to the uncategorized inbox
+    Inbox {
+        #[structopt()]
+        position: usize
+    },
+    /// Commit the journal file and pull-rebase/push it to its git remote
+    Sync,
+    /// Run an arbitrary git command scoped to the journal file's directory
+    Git {
+        #[structopt()]
+        args: Vec<String>
+    },
 }
 
 #[derive(Debug, StructOpt)]
@@ -40,4 +95,7 @@ pub struct CommandLineArgs {
     /// Use a different journal file.
     #[structopt(parse(from_os_str), short, long)]
     pub journal_file: Option<PathBuf>,
+    /// Storage format to use, overriding the file extension (json or msgpack).
+    #[structopt(long)]
+    pub format: Option<Format>,
 }
\ No newline at end of file
@@ -0,0 +1,71 @@
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Runs `git <args>` with its working directory set to `repo_dir`, returning
+/// stdout on success or an error carrying stderr on failure.
+fn run(repo_dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo_dir)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(Error::new(
+            ErrorKind::Other,
+            format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Stages the journal file, commits it if anything changed, then pulls with
+/// rebase and pushes. A failed pull surfaces as an error telling the user how
+/// to continue or abort the rebase; the repo is left as git left it.
+pub fn sync(journal_path: &Path, commit_message: &str) -> Result<()> {
+    let dir = journal_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let file_name = journal_path
+        .file_name()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "journal path has no file name"))?
+        .to_string_lossy()
+        .into_owned();
+
+    run(dir, &["add", "--", &file_name])?;
+
+    let status = run(dir, &["status", "--porcelain", "--", &file_name])?;
+    if !status.trim().is_empty() {
+        run(dir, &["commit", "-m", commit_message])?;
+    }
+
+    if let Err(e) = run(dir, &["pull", "--rebase"]) {
+        return Err(Error::new(
+            ErrorKind::Other,
+            format!(
+                "sync stopped during pull --rebase ({}); resolve the conflict and run `git rebase --continue`, or `git rebase --abort` to back out",
+                e
+            ),
+        ));
+    }
+
+    run(dir, &["push"])?;
+    Ok(())
+}
+
+/// Runs an arbitrary git command scoped to the journal's directory.
+pub fn passthrough(journal_path: &Path, args: &[String]) -> Result<()> {
+    let dir = journal_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = run(dir, &args)?;
+    print!("{}", output);
+    Ok(())
+}
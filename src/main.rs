@@ -1,38 +1,102 @@
 use anyhow::anyhow;
 
 mod cli;
+mod git;
 mod tasks;
 
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::iter::FromIterator;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
 use crate::cli::{Action::*, CommandLineArgs};
 use crate::tasks::Task;
 
+/// The default journal file, `rusty-journal/journal.json` under the data
+/// directory, honoring `XDG_DATA_HOME` (via `dirs::data_dir`) so the active
+/// journal and its archive live where the platform expects state.
 fn find_default_journal_file() -> Option<PathBuf> {
-    dirs::home_dir().map(|mut path|
-        {
-            path.push(".rusty-journal.json");
-            path
-        }
-    )
+    dirs::data_dir().map(|mut path| {
+        path.push("rusty-journal");
+        path.push("journal.json");
+        path
+    })
+}
+
+/// A sibling file namespaced by the journal's own stem (`foo.json` ->
+/// `foo.<suffix>`), so two `--journal-file`s in the same directory (e.g.
+/// `work.json` and `personal.json`) never share an archive or id counter.
+fn sibling_path(journal_file: &Path, suffix: &str) -> PathBuf {
+    let stem = journal_file
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    journal_file.with_file_name(format!("{}.{}", stem, suffix))
 }
 
 fn main() -> anyhow::Result<()> {
     let CommandLineArgs {
-        action, journal_file
+        action, journal_file, format
     } = CommandLineArgs::from_args();
 
     let journal_file = journal_file
         .or_else(find_default_journal_file)
         .ok_or(anyhow!("Failed to find journal file"))?;
+    if let Some(data_dir) = journal_file.parent() {
+        std::fs::create_dir_all(data_dir)?;
+    }
+    let archive_file = sibling_path(&journal_file, "finished.json");
+    let id_counter_file = sibling_path(&journal_file, "next_id");
+    let store = tasks::store_for(&journal_file, format);
+    let store = store.as_ref();
 
     match action {
-        Add { text } => tasks::add_task(journal_file, Task::new(text)),
-        Done { position } => tasks::complete_task(journal_file, position),
-        List => tasks::list_tasks(journal_file),
-        Edit { position, text } => tasks::edit_task(journal_file, position, text),
-        ListCompleted => tasks::list_completed_tasks(journal_file),
+        Add {
+            text,
+            priority,
+            tags,
+            dependencies,
+        } => tasks::add_task(
+            journal_file,
+            store,
+            archive_file,
+            id_counter_file,
+            Task::new(
+                text,
+                priority,
+                HashSet::from_iter(tags),
+                HashSet::from_iter(dependencies),
+            ),
+        ),
+        Done { position } => tasks::complete_task(journal_file, store, archive_file, position),
+        List { tag } => tasks::list_tasks(journal_file, store, tag),
+        Edit {
+            position,
+            text,
+            tags,
+            dependencies,
+        } => tasks::edit_task(
+            journal_file,
+            store,
+            position,
+            text,
+            HashSet::from_iter(tags),
+            HashSet::from_iter(dependencies),
+        ),
+        ListCompleted => tasks::list_completed_tasks(archive_file),
+        Log {
+            position,
+            hours,
+            minutes,
+        } => tasks::log_time(journal_file, store, position, hours, minutes),
+        Start { position } => tasks::start_task(journal_file, store, position),
+        Stop { position } => tasks::stop_task(journal_file, store, position),
+        Inbox { position } => tasks::inbox_task(journal_file, store, position),
+        Sync => {
+            let message = tasks::journal_summary(journal_file.clone(), store)?;
+            git::sync(&journal_file, &message)
+        }
+        Git { args } => git::passthrough(&journal_file, &args),
     }?;
     Ok(())
 }
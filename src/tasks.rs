@@ -1,18 +1,133 @@
 // use anyhow::Ok;
-use chrono::{serde::ts_seconds, DateTime, Local, Utc};
+use chrono::{serde::ts_seconds, DateTime, Local, NaiveDate, Utc};
+use colored::{ColoredString, Colorize};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::from_reader;
+use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
 use std::fs::{File, OpenOptions};
-use std::io::{Result, Seek, SeekFrom};
-use std::path::PathBuf;
+use std::io::{Read, Result, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use unicode_width::UnicodeWidthStr;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// How urgently a task needs attention, from least to most pressing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl Default for Priority {
+    fn default() -> Self {
+        Priority::Low
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "medium" => Ok(Priority::Medium),
+            "high" => Ok(Priority::High),
+            other => Err(format!("invalid priority '{}', expected low/medium/high", other)),
+        }
+    }
+}
+
+impl Priority {
+    fn colored(&self) -> ColoredString {
+        match self {
+            Priority::Low => "LOW".green(),
+            Priority::Medium => "MEDIUM".yellow(),
+            Priority::High => "HIGH".red(),
+        }
+    }
+}
+
+/// Where a task sits in the triage → active → done workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Status {
+    /// Uncategorized; not yet triaged into the working list.
+    Inbox,
+    /// Triaged and waiting to be worked on.
+    Pending,
+    /// Currently being worked on.
+    Active,
+    /// Finished.
+    Done,
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Status::Inbox
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Status::Inbox => "Inbox",
+            Status::Pending => "Pending",
+            Status::Active => "Active",
+            Status::Done => "Done",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// A block of time logged against a task on a given day.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TimeEntry {
+    pub logged_date: NaiveDate,
+    pub hours: u16,
+    pub minutes: u16,
+}
+
+impl TimeEntry {
+    /// Folds `minutes` over 60 into `hours`, erroring instead of overflowing
+    /// if the normalized hour count doesn't fit in a `u16`.
+    pub fn new(logged_date: NaiveDate, hours: u16, minutes: u16) -> Result<TimeEntry> {
+        let hours = hours.checked_add(minutes / 60).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "time value too large")
+        })?;
+        Ok(TimeEntry {
+            logged_date,
+            hours,
+            minutes: minutes % 60,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
 pub struct Task {
+    /// A stable identifier, assigned when the task is first added, that
+    /// other tasks can reference as a dependency even as positions shift.
+    #[serde(default)]
+    pub id: usize,
     pub text: String,
     #[serde(with = "ts_seconds")]
     pub create_at: DateTime<Utc>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub tags: HashSet<String>,
+    #[serde(default)]
+    pub dependencies: HashSet<usize>,
+    #[serde(default)]
+    pub time_entries: Vec<TimeEntry>,
+    #[serde(default)]
+    pub status: Status,
+    #[serde(
+        serialize_with = "serialize_optional_datetime",
+        deserialize_with = "deserialize_optional_datetime",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub started_at: Option<DateTime<Utc>>,
     #[serde(
         serialize_with = "serialize_optional_datetime",
         deserialize_with = "deserialize_optional_datetime",
@@ -21,6 +136,64 @@ pub struct Task {
     )]
     pub completed_at: Option<DateTime<Utc>>,
 }
+
+/// Mirrors `Task`, but leaves `status` as `Option` so a missing field can be
+/// told apart from one the repo has never seen at all. Journals written
+/// before `status` existed never had a chance to record it, but they may
+/// already have `completed_at` set — `Task`'s `Deserialize` impl below uses
+/// that to migrate such tasks to `Done` instead of quietly reopening them
+/// under a blind `Inbox` default.
+#[derive(Deserialize)]
+struct RawTask {
+    #[serde(default)]
+    id: usize,
+    text: String,
+    #[serde(with = "ts_seconds")]
+    create_at: DateTime<Utc>,
+    #[serde(default)]
+    priority: Priority,
+    #[serde(default)]
+    tags: HashSet<String>,
+    #[serde(default)]
+    dependencies: HashSet<usize>,
+    #[serde(default)]
+    time_entries: Vec<TimeEntry>,
+    #[serde(default)]
+    status: Option<Status>,
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    started_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_optional_datetime")]
+    completed_at: Option<DateTime<Utc>>,
+}
+
+impl<'de> Deserialize<'de> for Task {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = RawTask::deserialize(deserializer)?;
+        let status = raw
+            .status
+            .unwrap_or(if raw.completed_at.is_some() {
+                Status::Done
+            } else {
+                Status::Inbox
+            });
+        Ok(Task {
+            id: raw.id,
+            text: raw.text,
+            create_at: raw.create_at,
+            priority: raw.priority,
+            tags: raw.tags,
+            dependencies: raw.dependencies,
+            time_entries: raw.time_entries,
+            status,
+            started_at: raw.started_at,
+            completed_at: raw.completed_at,
+        })
+    }
+}
+
 fn serialize_optional_datetime<S>(
     date: &Option<DateTime<Utc>>,
     serializer: S,
@@ -59,129 +232,531 @@ impl Display for Task {
         let padding = if text_width < 80 { 80 - text_width } else { 0 };
 
         let padded_text = format!("{}{}", self.text, " ".repeat(padding));
+        let logged = self.total_logged_time();
+        let logged_suffix = if logged.0 > 0 || logged.1 > 0 {
+            format!(" (logged {}h {}m)", logged.0, logged.1)
+        } else {
+            String::new()
+        };
         match complete_at {
             Some(_) => write!(
                 f,
-                "{} [{}] (completed at {})",
+                "{} [{}] ({}){} (completed at {})",
                 padded_text,
                 created_at,
+                self.priority.colored(),
+                logged_suffix,
                 complete_at.unwrap()
             ),
-            None => write!(f, "{} [{}]", padded_text, created_at),
+            None => write!(
+                f,
+                "{} [{}] ({}){}",
+                padded_text,
+                created_at,
+                self.priority.colored(),
+                logged_suffix
+            ),
         }
     }
 }
 
 impl Task {
-    pub fn new(text: String) -> Task {
+    pub fn new(
+        text: String,
+        priority: Priority,
+        tags: HashSet<String>,
+        dependencies: HashSet<usize>,
+    ) -> Task {
         let create_at = Utc::now();
         Task {
+            id: 0,
             text,
             create_at,
+            priority,
+            tags,
+            dependencies,
+            time_entries: Vec::new(),
+            status: Status::Inbox,
+            started_at: None,
             completed_at: None,
         }
     }
+
+    /// Total time logged against this task, as `(hours, minutes)` with `minutes < 60`.
+    fn total_logged_time(&self) -> (u32, u32) {
+        let total_minutes: u32 = self
+            .time_entries
+            .iter()
+            .map(|e| e.hours as u32 * 60 + e.minutes as u32)
+            .sum();
+        (total_minutes / 60, total_minutes % 60)
+    }
 }
 
-fn collect_task(mut file: &File) -> Result<Vec<Task>> {
-    file.seek(SeekFrom::Start(0))?;
-    let tasks: Vec<Task> = match from_reader(file) {
-        Ok(tasks) => tasks,
-        Err(e) if e.is_eof() => Vec::new(),
-        Err(e) => Err(e)?,
+/// Reads and writes the full task list for a journal file, isolating the
+/// seek/truncate/serialize dance from the operations below.
+pub trait Store {
+    fn load(&self, file: &File) -> Result<Vec<Task>>;
+    fn save(&self, file: &File, tasks: &[Task]) -> Result<()>;
+}
+
+/// The original, human-readable encoding.
+pub struct JsonStore;
+
+impl Store for JsonStore {
+    fn load(&self, mut file: &File) -> Result<Vec<Task>> {
+        file.seek(SeekFrom::Start(0))?;
+        let tasks = match from_reader(file) {
+            Ok(tasks) => tasks,
+            Err(e) if e.is_eof() => Vec::new(),
+            Err(e) => Err(e)?,
+        };
+        file.seek(SeekFrom::Start(0))?;
+        Ok(tasks)
+    }
+
+    fn save(&self, mut file: &File, tasks: &[Task]) -> Result<()> {
+        file.seek(SeekFrom::Start(0))?;
+        file.set_len(0)?;
+        serde_json::to_writer(file, tasks)?;
+        Ok(())
+    }
+}
+
+/// A compact binary encoding, handy for journals too large to page through as JSON.
+pub struct MessagePackStore;
+
+impl Store for MessagePackStore {
+    fn load(&self, mut file: &File) -> Result<Vec<Task>> {
+        file.seek(SeekFrom::Start(0))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let tasks = if bytes.is_empty() {
+            Vec::new()
+        } else {
+            rmp_serde::from_slice(&bytes)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        };
+        file.seek(SeekFrom::Start(0))?;
+        Ok(tasks)
+    }
+
+    fn save(&self, mut file: &File, tasks: &[Task]) -> Result<()> {
+        let bytes = rmp_serde::to_vec(tasks)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        file.seek(SeekFrom::Start(0))?;
+        file.set_len(0)?;
+        file.write_all(&bytes)?;
+        Ok(())
+    }
+}
+
+/// Which on-disk encoding a journal file uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    MessagePack,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "messagepack" | "msgpack" => Ok(Format::MessagePack),
+            other => Err(format!("invalid format '{}', expected json/msgpack", other)),
+        }
+    }
+}
+
+/// Picks a `Store` for a journal file: an explicit `--format` wins, otherwise
+/// the file extension decides, defaulting to JSON.
+pub fn store_for(journal_path: &Path, format: Option<Format>) -> Box<dyn Store> {
+    let format = format.unwrap_or_else(|| {
+        match journal_path.extension().and_then(|e| e.to_str()) {
+            Some("msgpack") | Some("mpk") => Format::MessagePack,
+            _ => Format::Json,
+        }
+    });
+    match format {
+        Format::Json => Box::new(JsonStore),
+        Format::MessagePack => Box::new(MessagePackStore),
+    }
+}
+
+/// Hands out the next task id from a counter persisted at `counter_path`,
+/// independent of the active journal's current contents. The counter can't
+/// just be rederived from `max(active ids) + 1` on every call: completing a
+/// task removes it from the active file into the archive, so that max would
+/// "reset" downward and a later `add_task` could reuse an id an archived
+/// task still holds. The first time this runs (an empty or missing counter
+/// file), it seeds from the highest id already in use across both the
+/// active tasks and the archive, so upgrading an existing journal doesn't
+/// immediately collide either.
+fn next_task_id(counter_path: &Path, active: &[Task], archive_path: &Path) -> Result<usize> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open(counter_path)?;
+
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+
+    let next = match contents.trim().parse::<usize>() {
+        Ok(next) => next,
+        Err(_) => {
+            let archived_max = match OpenOptions::new().read(true).open(archive_path) {
+                Ok(archive_file) => JsonStore
+                    .load(&archive_file)?
+                    .iter()
+                    .map(|t| t.id)
+                    .max()
+                    .unwrap_or(0),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+                Err(e) => return Err(e),
+            };
+            let active_max = active.iter().map(|t| t.id).max().unwrap_or(0);
+            active_max.max(archived_max) + 1
+        }
     };
+
     file.seek(SeekFrom::Start(0))?;
-    Ok(tasks)
+    file.set_len(0)?;
+    write!(file, "{}", next + 1)?;
+
+    Ok(next)
 }
 
-pub fn add_task(journal_path: PathBuf, task: Task) -> Result<()> {
+pub fn add_task(
+    journal_path: PathBuf,
+    store: &dyn Store,
+    archive_path: PathBuf,
+    id_counter_path: PathBuf,
+    mut task: Task,
+) -> Result<()> {
     let file = OpenOptions::new()
         .read(true)
         .write(true)
         .create(true)
         .open(journal_path)?;
 
-    let mut tasks = collect_task(&file)?;
+    let mut tasks = store.load(&file)?;
+    task.id = next_task_id(&id_counter_path, &tasks, &archive_path)?;
     tasks.push(task);
-    serde_json::to_writer(file, &tasks)?;
+    store.save(&file, &tasks)?;
 
     Ok(())
 }
 
-pub fn complete_task(journal_path: PathBuf, task_position: usize) -> Result<()> {
+pub fn complete_task(
+    journal_path: PathBuf,
+    store: &dyn Store,
+    archive_path: PathBuf,
+    task_position: usize,
+) -> Result<()> {
     let file = OpenOptions::new()
         .read(true)
         .write(true)
         .open(journal_path)?;
-    let mut tasks = collect_task(&file)?;
-    let mut incomplete_tasks: Vec<_> = tasks
-        .iter_mut()
-        .filter(|t| t.completed_at.is_none())
+    let mut tasks = store.load(&file)?;
+    let incomplete_indices: Vec<usize> = tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.status != Status::Done)
+        .map(|(i, _)| i)
         .collect();
-    if task_position == 0 || task_position > incomplete_tasks.len() {
+    if task_position == 0 || task_position > incomplete_indices.len() {
         return Err(std::io::Error::new(
             std::io::ErrorKind::InvalidInput,
             "invalid task position",
         ));
     }
-    incomplete_tasks[task_position - 1].completed_at = Some(Utc::now());
+    let index = incomplete_indices[task_position - 1];
 
-    file.set_len(0)?;
-    serde_json::to_writer(file, &tasks)?;
+    let blocking: Vec<String> = tasks[index]
+        .dependencies
+        .iter()
+        .filter_map(|dep_id| tasks.iter().find(|t| t.id == *dep_id))
+        .filter(|t| t.status != Status::Done)
+        .map(|t| format!("#{} {}", t.id, t.text))
+        .collect();
+    if !blocking.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "cannot complete task: blocked by incomplete dependencies: {}",
+                blocking.join(", ")
+            ),
+        ));
+    }
+
+    let mut task = tasks.remove(index);
+    task.status = Status::Done;
+    task.completed_at = Some(Utc::now());
+    store.save(&file, &tasks)?;
+
+    let archive_file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open(archive_path)?;
+    let mut archived = JsonStore.load(&archive_file)?;
+    archived.push(task);
+    JsonStore.save(&archive_file, &archived)?;
+
+    Ok(())
+}
+
+pub fn start_task(journal_path: PathBuf, store: &dyn Store, task_position: usize) -> Result<()> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(journal_path)?;
+    let mut tasks = store.load(&file)?;
+    let open_indices: Vec<usize> = tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.status != Status::Done)
+        .map(|(i, _)| i)
+        .collect();
+    if task_position == 0 || task_position > open_indices.len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "invalid task position",
+        ));
+    }
+    let index = open_indices[task_position - 1];
+    if tasks[index].status == Status::Active {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "task is already active",
+        ));
+    }
+
+    tasks[index].status = Status::Active;
+    tasks[index].started_at = Some(Utc::now());
+
+    store.save(&file, &tasks)?;
     Ok(())
 }
 
-pub fn list_tasks(journal_path: PathBuf) -> Result<()> {
+pub fn stop_task(journal_path: PathBuf, store: &dyn Store, task_position: usize) -> Result<()> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(journal_path)?;
+    let mut tasks = store.load(&file)?;
+    let open_indices: Vec<usize> = tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.status != Status::Done)
+        .map(|(i, _)| i)
+        .collect();
+    if task_position == 0 || task_position > open_indices.len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "invalid task position",
+        ));
+    }
+    let index = open_indices[task_position - 1];
+    if tasks[index].status != Status::Active {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "task is not active",
+        ));
+    }
+
+    tasks[index].status = Status::Pending;
+
+    store.save(&file, &tasks)?;
+    Ok(())
+}
+
+pub fn inbox_task(journal_path: PathBuf, store: &dyn Store, task_position: usize) -> Result<()> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(journal_path)?;
+    let mut tasks = store.load(&file)?;
+    let open_indices: Vec<usize> = tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.status != Status::Done)
+        .map(|(i, _)| i)
+        .collect();
+    if task_position == 0 || task_position > open_indices.len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "invalid task position",
+        ));
+    }
+    let index = open_indices[task_position - 1];
+
+    tasks[index].status = Status::Inbox;
+    tasks[index].started_at = None;
+
+    store.save(&file, &tasks)?;
+    Ok(())
+}
+
+/// A one-line summary of task counts by status, suitable for a sync commit message.
+pub fn journal_summary(journal_path: PathBuf, store: &dyn Store) -> Result<String> {
+    let file = OpenOptions::new().read(true).open(journal_path)?;
+    let tasks = store.load(&file)?;
+    let count = |status: Status| tasks.iter().filter(|t| t.status == status).count();
+
+    Ok(format!(
+        "Sync journal: {} inbox, {} pending, {} active, {} done ({})",
+        count(Status::Inbox),
+        count(Status::Pending),
+        count(Status::Active),
+        count(Status::Done),
+        Local::now().format("%F %H:%M:%S")
+    ))
+}
+
+pub fn log_time(
+    journal_path: PathBuf,
+    store: &dyn Store,
+    task_position: usize,
+    hours: u16,
+    minutes: u16,
+) -> Result<()> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(journal_path)?;
+    let mut tasks = store.load(&file)?;
+    let incomplete_indices: Vec<usize> = tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.status != Status::Done)
+        .map(|(i, _)| i)
+        .collect();
+    if task_position == 0 || task_position > incomplete_indices.len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "invalid task position",
+        ));
+    }
+    let index = incomplete_indices[task_position - 1];
+    let logged_date = Local::now().date_naive();
+    tasks[index]
+        .time_entries
+        .push(TimeEntry::new(logged_date, hours, minutes)?);
+
+    store.save(&file, &tasks)?;
+    Ok(())
+}
+
+/// For each task (by its index into `tasks`), its 1-based position among all
+/// non-Done tasks in file order, or `0` for a Done task. This is the same
+/// number `start_task`/`stop_task`/`complete_task`/`edit_task`/`log_time`
+/// resolve their `position` argument against, so any listing that prints one
+/// of these positions next to a task can trust it round-trips back in.
+fn non_done_positions(tasks: &[Task]) -> Vec<usize> {
+    let mut positions = vec![0usize; tasks.len()];
+    let mut next = 1;
+    for (i, task) in tasks.iter().enumerate() {
+        if task.status != Status::Done {
+            positions[i] = next;
+            next += 1;
+        }
+    }
+    positions
+}
+
+pub fn list_tasks(journal_path: PathBuf, store: &dyn Store, tag: Option<String>) -> Result<()> {
     let file = OpenOptions::new().read(true).open(journal_path)?;
-    let tasks = collect_task(&file)?;
+    let tasks = store.load(&file)?;
 
     if tasks.is_empty() {
         println!("Task list is empty!");
     } else {
-        let mut order: u32 = 1;
-        for task in tasks.iter().filter(|t| t.completed_at.is_none()) {
-            println!("{}. {}", order, task);
-            order += 1;
+        let positions = non_done_positions(&tasks);
+
+        for status in [Status::Inbox, Status::Pending, Status::Active] {
+            let mut group: Vec<_> = tasks
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| t.status == status)
+                .filter(|(_, t)| tag.as_ref().map_or(true, |tag| t.tags.contains(tag)))
+                .collect();
+            if group.is_empty() {
+                continue;
+            }
+            group.sort_by(|(_, a), (_, b)| b.priority.cmp(&a.priority).then(a.create_at.cmp(&b.create_at)));
+
+            println!("-- {} --", status);
+            for (i, task) in group {
+                println!("{}. {}", positions[i], task);
+            }
         }
     }
     Ok(())
 }
-pub fn list_completed_tasks(journal_path: PathBuf) -> Result<()> {
-    let file = OpenOptions::new().read(true).open(journal_path)?;
-    let tasks = collect_task(&file)?;
+/// The archive always uses the JSON encoding, independent of the active
+/// journal's `--format`, since it's meant to be a small, inspectable history.
+pub fn list_completed_tasks(archive_path: PathBuf) -> Result<()> {
+    let file = match OpenOptions::new().read(true).open(archive_path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            println!("Task list is empty!");
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+    let tasks = JsonStore.load(&file)?;
 
     if tasks.is_empty() {
         println!("Task list is empty!");
     } else {
         let mut order: u32 = 1;
-        for task in tasks.iter().filter(|t| t.completed_at.is_some()) {
+        for task in &tasks {
             println!("{}. {}", order, task);
             order += 1;
         }
     }
     Ok(())
 }
-pub fn edit_task(journal_path: PathBuf, task_position: usize, text: String) -> Result<()> {
+pub fn edit_task(
+    journal_path: PathBuf,
+    store: &dyn Store,
+    task_position: usize,
+    text: String,
+    tags: HashSet<String>,
+    dependencies: HashSet<usize>,
+) -> Result<()> {
     // 只能修改未完成的任务，而且 task_position 需要排除已完成的任务
     let file = OpenOptions::new()
         .read(true)
         .write(true)
         .open(journal_path)?;
 
-    let mut tasks = collect_task(&file)?;
-    let mut incomplete_tasks: Vec<_> = tasks
-        .iter_mut()
-        .filter(|t| t.completed_at.is_none())
+    let mut tasks = store.load(&file)?;
+    let incomplete_indices: Vec<usize> = tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.status != Status::Done)
+        .map(|(i, _)| i)
         .collect();
-    if task_position == 0 || task_position > incomplete_tasks.len() {
+    if task_position == 0 || task_position > incomplete_indices.len() {
         return Err(std::io::Error::new(
             std::io::ErrorKind::InvalidInput,
             "invalid task position",
         ));
     };
-    incomplete_tasks[task_position - 1].text = text;
-    file.set_len(0)?;
-    serde_json::to_writer(file, &tasks)?;
+    let index = incomplete_indices[task_position - 1];
+    tasks[index].text = text;
+    if !tags.is_empty() {
+        tasks[index].tags = tags;
+    }
+    if !dependencies.is_empty() {
+        tasks[index].dependencies = dependencies;
+    }
+    store.save(&file, &tasks)?;
 
     Ok(())
 }
@@ -192,35 +767,133 @@ mod tests {
     use std::fs::File;
     use tempfile::tempdir;
 
+    fn task_with_status(status: Status) -> Task {
+        Task {
+            id: 0,
+            text: String::from("task"),
+            create_at: Utc::now(),
+            priority: Priority::Low,
+            tags: HashSet::new(),
+            dependencies: HashSet::new(),
+            time_entries: Vec::new(),
+            status,
+            started_at: None,
+            completed_at: None,
+        }
+    }
+
     #[test]
-    fn test_list_completed_tasks() -> Result<()> {
-        // Create a temporary directory
+    fn test_deserialize_pre_status_journal_migrates_completed_tasks() {
+        // A journal written before `status` existed: no `status` field, but
+        // `completed_at` is already populated for a finished task.
+        let old_json = r#"{
+            "id": 1,
+            "text": "old task",
+            "create_at": 1600000000,
+            "completed_at": 1600000100
+        }"#;
+        let task: Task = serde_json::from_str(old_json).unwrap();
+        assert_eq!(task.status, Status::Done);
+
+        let open_json = r#"{
+            "id": 2,
+            "text": "still open",
+            "create_at": 1600000000
+        }"#;
+        let task: Task = serde_json::from_str(open_json).unwrap();
+        assert_eq!(task.status, Status::Inbox);
+    }
+
+    #[test]
+    fn test_non_done_positions_span_status_groups() {
+        // list_tasks prints these tasks under separate "-- Inbox --"/"--
+        // Pending --"/"-- Active --" headers, but the position next to each
+        // one must still be its index in one flat non-Done list, since
+        // that's what start/stop/done/edit/log resolve "position" against.
+        let tasks = vec![
+            task_with_status(Status::Inbox),
+            task_with_status(Status::Active),
+            task_with_status(Status::Done),
+            task_with_status(Status::Pending),
+        ];
+        assert_eq!(non_done_positions(&tasks), vec![1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn test_messagepack_store_round_trip() -> Result<()> {
         let dir = tempdir()?;
-        let file_path = dir.path().join("test_journal.json");
+        let journal_path = dir.path().join("test_journal.mpk");
 
-        // Create a sample task list with one completed task
         let tasks = vec![
-            Task {
-                text: String::from("Task 1"),
-                create_at: Utc::now(),
-                completed_at: Some(Utc::now()),
-            },
-            Task {
-                text: String::from("Task 2"),
-                create_at: Utc::now(),
-                completed_at: None,
-            },
+            task_with_status(Status::Pending),
+            task_with_status(Status::Active),
         ];
 
-        // Write the tasks to the file
-        let file = File::create(&file_path)?;
-        serde_json::to_writer(&file, &tasks)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(&journal_path)?;
+        MessagePackStore.save(&file, &tasks)?;
+
+        let loaded = MessagePackStore.load(&file)?;
+        assert_eq!(loaded.len(), tasks.len());
+        assert_eq!(loaded[0].status, Status::Pending);
+        assert_eq!(loaded[1].status, Status::Active);
+
+        // `store_for` should also pick MessagePack from the file extension,
+        // without an explicit `--format`.
+        assert!(store_for(&journal_path, None).load(&file)?.len() == tasks.len());
+
+        dir.close()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_completed_tasks() -> Result<()> {
+        // Create a temporary directory
+        let dir = tempdir()?;
+        let journal_path = dir.path().join("test_journal.json");
+        let archive_path = dir.path().join("finished.json");
+
+        // The active journal holds one pending task...
+        let active_tasks = vec![Task {
+            id: 0,
+            text: String::from("Task 2"),
+            create_at: Utc::now(),
+            priority: Priority::Low,
+            tags: HashSet::new(),
+            dependencies: HashSet::new(),
+            time_entries: Vec::new(),
+            status: Status::Pending,
+            started_at: None,
+            completed_at: None,
+        }];
+        // ...and the archive holds one already-completed task.
+        let archived_tasks = vec![Task {
+            id: 0,
+            text: String::from("Task 1"),
+            create_at: Utc::now(),
+            priority: Priority::Low,
+            tags: HashSet::new(),
+            dependencies: HashSet::new(),
+            time_entries: Vec::new(),
+            status: Status::Done,
+            started_at: None,
+            completed_at: Some(Utc::now()),
+        }];
+
+        let file = File::create(&journal_path)?;
+        serde_json::to_writer(&file, &active_tasks)?;
+        file.sync_all()?;
+        let file = File::create(&archive_path)?;
+        serde_json::to_writer(&file, &archived_tasks)?;
         file.sync_all()?;
 
-        list_tasks(file_path.clone())?;
+        list_tasks(journal_path.clone(), &JsonStore, None)?;
 
         // Call the function to list completed tasks
-        list_completed_tasks(file_path.clone())?;
+        list_completed_tasks(archive_path.clone())?;
 
         // Clean up
         dir.close()?;
@@ -231,70 +904,143 @@ mod tests {
     fn test_complete_task() -> Result<()> {
         // Create a temporary directory
         let dir = tempdir()?;
-        let file_path = dir.path().join("test_journal.json");
+        let journal_path = dir.path().join("test_journal.json");
+        let archive_path = dir.path().join("finished.json");
 
-        // Create a sample task list with one incomplete task
+        // Create a sample task list of incomplete tasks
         let tasks = vec![
             Task {
+                id: 0,
                 text: String::from("Task 1"),
                 create_at: Utc::now(),
+                priority: Priority::Low,
+                tags: HashSet::new(),
+                dependencies: HashSet::new(),
+                time_entries: Vec::new(),
+                status: Status::Pending,
+                started_at: None,
                 completed_at: None,
             },
             Task {
+                id: 0,
                 text: String::from("Task 2"),
                 create_at: Utc::now(),
+                priority: Priority::Low,
+                tags: HashSet::new(),
+                dependencies: HashSet::new(),
+                time_entries: Vec::new(),
+                status: Status::Pending,
+                started_at: None,
                 completed_at: None,
             },
             Task {
-                text: String::from("Task 3"),
-                create_at: Utc::now(),
-                completed_at: Some(Utc::now()),
-            },
-            Task {
+                id: 0,
                 text: String::from("Task 4"),
                 create_at: Utc::now(),
+                priority: Priority::Low,
+                tags: HashSet::new(),
+                dependencies: HashSet::new(),
+                time_entries: Vec::new(),
+                status: Status::Pending,
+                started_at: None,
                 completed_at: None,
             },
         ];
 
         // Write the tasks to the file
-        let file = File::create(&file_path)?;
+        let file = File::create(&journal_path)?;
         serde_json::to_writer(&file, &tasks)?;
         file.sync_all()?;
 
-        list_tasks(file_path.clone())?;
+        list_tasks(journal_path.clone(), &JsonStore, None)?;
         println!("Complete task 1");
-        // Complete the first task
-        complete_task(file_path.clone(), 1)?;
+        // Complete the first task; it should move out of the active journal
+        // and into the archive.
+        complete_task(journal_path.clone(), &JsonStore, archive_path.clone(), 1)?;
 
-        // Read the tasks back from the file
-        let file = File::open(&file_path)?;
-        let tasks: Vec<Task> = from_reader(file)?;
+        let file = File::open(&journal_path)?;
+        let active: Vec<Task> = from_reader(file)?;
+        assert_eq!(active.len(), 2);
+        assert_eq!(active[0].text, "Task 2");
+        assert_eq!(active[1].text, "Task 4");
 
-        // Check that the first task is completed
-        assert!(tasks[0].completed_at.is_some());
-        assert!(tasks[1].completed_at.is_none());
-
-        list_tasks(file_path.clone())?;
+        list_tasks(journal_path.clone(), &JsonStore, None)?;
         println!("Complete task 4");
-        complete_task(file_path.clone(), 2)?;
+        // Position 2 now refers to "Task 4", since "Task 1" already left the active journal.
+        complete_task(journal_path.clone(), &JsonStore, archive_path.clone(), 2)?;
         println!("Left");
-        list_tasks(file_path.clone())?;
+        list_tasks(journal_path.clone(), &JsonStore, None)?;
 
         println!("Already complete ----");
-        list_completed_tasks(file_path.clone())?;
+        list_completed_tasks(archive_path.clone())?;
 
-        let file = File::open(&file_path)?;
-        let tasks: Vec<Task> = from_reader(file)?;
+        let file = File::open(&journal_path)?;
+        let active: Vec<Task> = from_reader(file)?;
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].text, "Task 2");
 
-        assert_eq!(tasks.len(), 4);
-        assert!(tasks[0].completed_at.is_some());
-        assert!(tasks[1].completed_at.is_none());
-        assert!(tasks[2].completed_at.is_some());
-        assert!(tasks[3].completed_at.is_some());
+        let file = File::open(&archive_path)?;
+        let archived: Vec<Task> = from_reader(file)?;
+        assert_eq!(archived.len(), 2);
+        assert!(archived.iter().all(|t| t.status == Status::Done));
 
         // Clean up
         dir.close()?;
         Ok(())
     }
+
+    #[test]
+    fn test_complete_task_respects_dependencies() -> Result<()> {
+        let dir = tempdir()?;
+        let journal_path = dir.path().join("test_journal.json");
+        let archive_path = dir.path().join("finished.json");
+
+        // Task 2 depends on Task 1.
+        let tasks = vec![
+            Task {
+                id: 1,
+                text: String::from("Task 1"),
+                create_at: Utc::now(),
+                priority: Priority::Low,
+                tags: HashSet::new(),
+                dependencies: HashSet::new(),
+                time_entries: Vec::new(),
+                status: Status::Pending,
+                started_at: None,
+                completed_at: None,
+            },
+            Task {
+                id: 2,
+                text: String::from("Task 2"),
+                create_at: Utc::now(),
+                priority: Priority::Low,
+                tags: HashSet::new(),
+                dependencies: HashSet::from([1]),
+                time_entries: Vec::new(),
+                status: Status::Pending,
+                started_at: None,
+                completed_at: None,
+            },
+        ];
+
+        let file = File::create(&journal_path)?;
+        serde_json::to_writer(&file, &tasks)?;
+        file.sync_all()?;
+
+        // Task 2 (position 2) is blocked until Task 1 is done.
+        let err = complete_task(journal_path.clone(), &JsonStore, archive_path.clone(), 2)
+            .expect_err("completing a task with an incomplete dependency should fail");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+        // Completing Task 1 (position 1) unblocks Task 2, now at position 1.
+        complete_task(journal_path.clone(), &JsonStore, archive_path.clone(), 1)?;
+        complete_task(journal_path.clone(), &JsonStore, archive_path.clone(), 1)?;
+
+        let archived: Vec<Task> = from_reader(File::open(&archive_path)?)?;
+        assert_eq!(archived.len(), 2);
+        assert!(archived.iter().all(|t| t.status == Status::Done));
+
+        dir.close()?;
+        Ok(())
+    }
 }